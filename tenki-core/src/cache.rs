@@ -0,0 +1,160 @@
+//! 取得済みの天気予報を地点ごとに一定時間使い回すキャッシュ。
+//!
+//! tenki.jpの発表は数分〜数十分おきにしか更新されないため、UIがポーリングする
+//! たびに毎回スクレイピングし直すのは無駄が多く、頻度次第ではレート制限される
+//! おそれもある。そこで`Location`をキーに、パース済みの予報を
+//! TTL（デフォルト10分）の間だけ使い回す。取得間隔ごとの区別はキーに含まれないため、
+//! 3時間天気・1時間天気・10日間天気はそれぞれ別の`Cache`を持つ。
+
+use crate::location::Location;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// デフォルトのキャッシュ有効期間（10分）。
+pub const DEFAULT_TTL: Duration = Duration::from_secs(10 * 60);
+
+struct Entry<T> {
+    value: T,
+    fetched_at: Instant,
+}
+
+/// 地点ごとにパース済みの値をTTL付きで保持するキャッシュ。
+pub struct Cache<T> {
+    ttl: Duration,
+    /// falseなら常に`fetch`を呼ぶ。テストなどでキャッシュそのものを無効化したい
+    /// 場合に[`Cache::disabled`]で立てる、このキャッシュ1本だけに効くスイッチ。
+    enabled: bool,
+    entries: Mutex<HashMap<Location, Entry<T>>>,
+}
+
+impl<T: Clone> Cache<T> {
+    pub fn new(ttl: Duration) -> Self {
+        Cache {
+            ttl,
+            enabled: true,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// キャッシュを無効化した状態で作る。常に`fetch`が呼ばれる。
+    pub fn disabled(ttl: Duration) -> Self {
+        Cache {
+            ttl,
+            enabled: false,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// TTL以内にキャッシュされた値があればそれを返す。
+    /// [`Cache::disabled`]で作られている間は常に`None`。
+    pub fn peek(&self, location: &Location) -> Option<T> {
+        if !self.enabled {
+            return None;
+        }
+
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(location)
+            .filter(|entry| entry.fetched_at.elapsed() < self.ttl)
+            .map(|entry| entry.value.clone())
+    }
+
+    /// 取得した値をキャッシュに保存する。
+    pub fn store(&self, location: &Location, value: T) {
+        self.entries.lock().unwrap().insert(
+            *location,
+            Entry {
+                value,
+                fetched_at: Instant::now(),
+            },
+        );
+    }
+
+    /// キャッシュにヒットすればそれを返し、なければ`fetch`を呼んで結果を保存する。
+    pub fn get_or_fetch<E>(
+        &self,
+        location: &Location,
+        fetch: impl FnOnce() -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E> {
+        if let Some(cached) = self.peek(location) {
+            return Ok(cached);
+        }
+
+        let value = fetch()?;
+        self.store(location, value.clone());
+        Ok(value)
+    }
+
+    /// 指定した地点のキャッシュを破棄する。次回呼び出し時に再取得される。
+    pub fn invalidate(&self, location: &Location) {
+        self.entries.lock().unwrap().remove(location);
+    }
+
+    /// 指定した地点のキャッシュを破棄した上で、即座に`fetch`で取得し直す。
+    pub fn refresh<E>(
+        &self,
+        location: &Location,
+        fetch: impl FnOnce() -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E> {
+        self.invalidate(location);
+        self.get_or_fetch(location, fetch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_hit_within_ttl() {
+        let cache = Cache::new(Duration::from_secs(60));
+        let location = Location::TOKYO;
+
+        let mut calls = 0;
+        for _ in 0..3 {
+            cache
+                .get_or_fetch(&location, || {
+                    calls += 1;
+                    Ok::<_, ()>(42)
+                })
+                .unwrap();
+        }
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_invalidate_forces_refetch() {
+        let cache = Cache::new(Duration::from_secs(60));
+        let location = Location::TOKYO;
+
+        cache.get_or_fetch(&location, || Ok::<_, ()>(1)).unwrap();
+        cache.invalidate(&location);
+
+        let mut calls = 0;
+        cache
+            .get_or_fetch(&location, || {
+                calls += 1;
+                Ok::<_, ()>(2)
+            })
+            .unwrap();
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_disabled_caching_always_refetches() {
+        let cache = Cache::disabled(Duration::from_secs(60));
+        let location = Location::TOKYO;
+
+        let mut calls = 0;
+        for _ in 0..3 {
+            cache
+                .get_or_fetch(&location, || {
+                    calls += 1;
+                    Ok::<_, ()>(0)
+                })
+                .unwrap();
+        }
+        assert_eq!(calls, 3);
+    }
+}
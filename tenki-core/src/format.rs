@@ -0,0 +1,186 @@
+//! `Announce`をプレースホルダ付きテンプレート文字列からレンダリングするフォーマッタ。
+//!
+//! テンプレートには次のプレースホルダが使える:
+//! - `$hour` 時刻（e.g. `09`）
+//! - `$icon` 天気種別に対応する絵文字アイコン
+//! - `$kind` 天気種別の表示名
+//! - `$temp` 気温
+//! - `$prob_precip` 降水確率
+//! - `$precipitation` 降水量
+//! - `$humidity` 湿度
+//! - `$wind_dir` 風向
+//! - `$wind_speed` 風速
+//!
+//! ステータスバーやCLIがそのまま表示できるように、主表示用(`primary`)と
+//! 簡易表示用(`alternate`)の2つのテンプレートを持たせてトグルできるように
+//! してある。`clean`モードでは数値（気温・降水確率・降水量・湿度・風速）の単位を省く。
+//! `$icon`・`$kind`・`$wind_dir`は`clean`モードでも表示用の文字列のまま埋め込まれる。
+
+use crate::weather::{Announce, Weather, WeatherKind};
+use chrono::{NaiveTime, Timelike};
+
+/// primary/alternateの2本立てのテンプレート。
+pub struct Template {
+    /// 通常表示用。単位の有無はどちらのテンプレートでも`clean`モードで決まる
+    pub primary: String,
+    /// 簡易表示用。UI側でトグルする想定
+    pub alternate: String,
+}
+
+impl Template {
+    pub fn new(primary: impl Into<String>, alternate: impl Into<String>) -> Self {
+        Template {
+            primary: primary.into(),
+            alternate: alternate.into(),
+        }
+    }
+}
+
+pub struct Formatter {
+    pub template: Template,
+    /// trueの場合、数値の単位を省いた値を埋め込む（機械向け）
+    clean: bool,
+}
+
+impl Formatter {
+    pub fn new(template: Template) -> Self {
+        Formatter {
+            template,
+            clean: false,
+        }
+    }
+
+    /// `clean`モードを切り替える。
+    pub fn clean(mut self, clean: bool) -> Self {
+        self.clean = clean;
+        self
+    }
+
+    /// `hour`時点の`announce`を、`alternate`がtrueなら簡易テンプレートで、
+    /// falseなら通常テンプレートでレンダリングする。
+    pub fn format(&self, hour: NaiveTime, announce: &Announce, alternate: bool) -> String {
+        let tmpl = if alternate {
+            &self.template.alternate
+        } else {
+            &self.template.primary
+        };
+
+        match announce {
+            Announce::NotYet => tmpl
+                .replace("$hour", &format!("{:02}", hour.hour()))
+                .replace("$icon", "?")
+                .replace("$kind", "---")
+                .replace("$temp", "---")
+                .replace("$prob_precip", "---")
+                .replace("$precipitation", "---")
+                .replace("$humidity", "---")
+                .replace("$wind_dir", "---")
+                .replace("$wind_speed", "---"),
+            Announce::Past(weather) | Announce::Regular(weather) => {
+                self.render(tmpl, hour, weather)
+            }
+        }
+    }
+
+    fn render(&self, tmpl: &str, hour: NaiveTime, weather: &Weather) -> String {
+        tmpl.replace("$hour", &format!("{:02}", hour.hour()))
+            .replace("$icon", icon(weather.kind))
+            .replace("$kind", &weather.kind.to_string())
+            .replace("$temp", &self.value(weather.temperature, "°C"))
+            .replace(
+                "$prob_precip",
+                &weather
+                    .prob_precip
+                    .map(|p| self.value(f32::from(p), "%"))
+                    .unwrap_or_else(|| "---".to_owned()),
+            )
+            .replace("$precipitation", &self.value(weather.precipitation, "mm/h"))
+            .replace("$humidity", &self.value(f32::from(weather.humidity), "%"))
+            .replace("$wind_dir", &weather.wind_direction.to_string())
+            .replace("$wind_speed", &self.value(weather.wind_speed, "m/s"))
+    }
+
+    fn value(&self, value: f32, unit: &str) -> String {
+        if self.clean {
+            format!("{}", value)
+        } else {
+            format!("{}{}", value, unit)
+        }
+    }
+}
+
+/// 天気種別に対応する絵文字アイコン。
+fn icon(kind: WeatherKind) -> &'static str {
+    use WeatherKind::*;
+    match kind {
+        Sunny => "☀",
+        SunnyOccasionallyCloudy | SunnyTemporarilyCloudy => "🌤",
+        SunnyOccasionallyRain | SunnyTemporarilyRain => "🌦",
+        Cloudy => "☁",
+        CloudyOccasionallySunny | CloudyTemporarilySunny => "⛅",
+        CloudyOccasionallyRain | CloudyTemporarilyRain => "🌥",
+        Rain | RainOccasionallySunny | RainOccasionallyCloudy => "🌧",
+        Snow | SnowOccasionallySunny | SnowOccasionallyCloudy => "❄",
+        Thunderstorm => "⛈",
+        Fog => "🌫",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::WindDirection;
+
+    fn weather() -> Weather {
+        Weather {
+            kind: WeatherKind::Sunny,
+            temperature: 28.0,
+            temperature_low: None,
+            prob_precip: Some(10),
+            precipitation: 0.0,
+            humidity: 55,
+            wind_direction: WindDirection::North,
+            wind_speed: 2.0,
+        }
+    }
+
+    #[test]
+    fn test_primary_template() {
+        let formatter = Formatter::new(Template::new("$hour時 $kind $temp", "$icon $temp"));
+        let rendered = formatter.format(
+            NaiveTime::from_hms(9, 0, 0),
+            &Announce::Regular(weather()),
+            false,
+        );
+        assert_eq!(rendered, "09時 晴れ 28°C");
+    }
+
+    #[test]
+    fn test_alternate_template() {
+        let formatter = Formatter::new(Template::new("$hour時 $kind $temp", "$icon $temp"));
+        let rendered = formatter.format(
+            NaiveTime::from_hms(9, 0, 0),
+            &Announce::Regular(weather()),
+            true,
+        );
+        assert_eq!(rendered, "☀ 28°C");
+    }
+
+    #[test]
+    fn test_clean_mode_drops_units() {
+        let formatter = Formatter::new(Template::new("$temp", "$temp")).clean(true);
+        let rendered = formatter.format(
+            NaiveTime::from_hms(9, 0, 0),
+            &Announce::Regular(weather()),
+            false,
+        );
+        assert_eq!(rendered, "28");
+    }
+
+    #[test]
+    fn test_not_yet() {
+        let formatter = Formatter::new(Template::new("$hour時 $kind", "$kind"));
+        let rendered = formatter.format(NaiveTime::from_hms(21, 0, 0), &Announce::NotYet, false);
+        assert_eq!(rendered, "21時 ---");
+    }
+}
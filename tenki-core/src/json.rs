@@ -0,0 +1,82 @@
+//! `DailyForecast`（やその配列）をJSONとして出し入れするためのヘルパー。
+//!
+//! HTTPサービスのレスポンスにしたり、取得結果をディスクにキャッシュしたりする
+//! 用途を想定している。天気予報モデル専用のヘルパーなので、
+//! 他の`Serialize`/`Deserialize`型にまで`.to_json()`を生やしたりはしない。
+
+use crate::weather::DailyForecast;
+use serde::Deserialize;
+
+impl DailyForecast {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(s: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(s)
+    }
+}
+
+/// `fetch_each_3hours_forecast`等が返す`Box<[DailyForecast; N]>`をまとめてJSON化する。
+pub fn forecasts_to_json<const N: usize>(
+    forecasts: &[DailyForecast; N],
+) -> serde_json::Result<String> {
+    serde_json::to_string(forecasts)
+}
+
+/// [`forecasts_to_json`]の逆変換。
+pub fn forecasts_from_json<const N: usize>(s: &str) -> serde_json::Result<Box<[DailyForecast; N]>>
+where
+    [DailyForecast; N]: for<'de> Deserialize<'de>,
+{
+    serde_json::from_str(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::{Announce, Weather, WeatherKind, WindDirection};
+
+    fn forecast() -> DailyForecast {
+        DailyForecast {
+            location: "東京 (7月26日 5時発表)".to_owned(),
+            date: chrono::NaiveDate::from_ymd(2026, 7, 26),
+            weathers: vec![
+                (chrono::NaiveTime::from_hms(9, 0, 0), Announce::NotYet),
+                (
+                    chrono::NaiveTime::from_hms(12, 0, 0),
+                    Announce::Regular(Weather {
+                        kind: WeatherKind::Sunny,
+                        temperature: 30.0,
+                        temperature_low: None,
+                        prob_precip: Some(10),
+                        precipitation: 0.0,
+                        humidity: 55,
+                        wind_direction: WindDirection::North,
+                        wind_speed: 2.0,
+                    }),
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_daily_forecast_json_roundtrip() {
+        let forecast = forecast();
+
+        let json = forecast.to_json().unwrap();
+        let roundtripped = DailyForecast::from_json(&json).unwrap();
+        assert_eq!(roundtripped.location, forecast.location);
+        assert_eq!(roundtripped.date, forecast.date);
+        assert_eq!(roundtripped.weathers.len(), forecast.weathers.len());
+    }
+
+    #[test]
+    fn test_forecasts_array_json_roundtrip() {
+        let forecasts: [DailyForecast; 3] = [forecast(), forecast(), forecast()];
+
+        let json = forecasts_to_json(&forecasts).unwrap();
+        let roundtripped: Box<[DailyForecast; 3]> = forecasts_from_json(&json).unwrap();
+        assert_eq!(roundtripped.len(), 3);
+    }
+}
@@ -0,0 +1,7 @@
+pub mod cache;
+pub mod format;
+pub mod json;
+pub mod location;
+pub mod nowcast;
+pub mod scrape;
+pub mod weather;
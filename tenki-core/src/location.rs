@@ -0,0 +1,202 @@
+//! tenki.jpの地点コード（地方/都道府県/地域/地点）を表す`Location`。
+//!
+//! tenki.jpのURLは `forecast/{region}/{prefecture}/{subregion}/{point}/...` という
+//! 階層的なコードで地点を指定する。このモジュールはそのコードを型として扱い、
+//! 緯度経度や都市名、IPアドレスからの解決手段を提供する。
+
+#[derive(Debug)]
+pub enum Error {
+    NetworkError { msg: String },
+    NotFound { msg: String },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NetworkError { msg } => write!(f, "Network Error: {}", msg),
+            Error::NotFound { msg } => write!(f, "Location Not Found: {}", msg),
+        }
+    }
+}
+
+/// tenki.jpの地点コード
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Location {
+    pub region: u32,
+    pub prefecture: u32,
+    pub subregion: u32,
+    pub point: u32,
+}
+
+/// 都市名・緯度経度からtenki.jpの地点コードを引くための最小限のテーブル。
+/// 実際には全国の地点を網羅していないが、主要都市だけは自力で解決できるようにしておき、
+/// それ以外はIP位置情報か明示的な`Location`指定に頼る。
+const KNOWN_POINTS: &[(&str, f64, f64, Location)] = &[
+    (
+        "東京",
+        35.6812,
+        139.7671,
+        Location {
+            region: 3,
+            prefecture: 11,
+            subregion: 4020,
+            point: 8220,
+        },
+    ),
+    (
+        "大阪",
+        34.6937,
+        135.5023,
+        Location {
+            region: 6,
+            prefecture: 27,
+            subregion: 6200,
+            point: 8438,
+        },
+    ),
+    (
+        "札幌",
+        43.0618,
+        141.3545,
+        Location {
+            region: 1,
+            prefecture: 1,
+            subregion: 1400,
+            point: 8440,
+        },
+    ),
+    (
+        "福岡",
+        33.5904,
+        130.4017,
+        Location {
+            region: 9,
+            prefecture: 40,
+            subregion: 9300,
+            point: 8190,
+        },
+    ),
+];
+
+impl Location {
+    /// 東京（新宿区）。明示的な地点指定がない場合のデフォルト（`Location::default()`）。
+    pub const TOKYO: Location = Location {
+        region: 3,
+        prefecture: 11,
+        subregion: 4020,
+        point: 8220,
+    };
+
+    pub fn new(region: u32, prefecture: u32, subregion: u32, point: u32) -> Self {
+        Location {
+            region,
+            prefecture,
+            subregion,
+            point,
+        }
+    }
+
+    /// tenki.jpのURLに埋め込む `{region}/{prefecture}/{subregion}/{point}` の形式を返す。
+    pub fn path(&self) -> String {
+        format!(
+            "{}/{}/{}/{}",
+            self.region, self.prefecture, self.subregion, self.point
+        )
+    }
+
+    /// 都市名から最寄りの地点コードを引く。未知の都市名は`Error::NotFound`になる。
+    pub fn from_city_name(name: &str) -> Result<Self> {
+        KNOWN_POINTS
+            .iter()
+            .find(|(city, ..)| *city == name)
+            .map(|(_, _, _, location)| *location)
+            .ok_or_else(|| Error::NotFound {
+                msg: format!("no known tenki.jp point for city {:?}", name),
+            })
+    }
+
+    /// 緯度経度から最も近い地点コードを引く。
+    pub fn from_lat_lon(lat: f64, lon: f64) -> Result<Self> {
+        KNOWN_POINTS
+            .iter()
+            .min_by(|(_, lat_a, lon_a, _), (_, lat_b, lon_b, _)| {
+                let dist = |lat_p: f64, lon_p: f64| {
+                    let dlat = lat_p - lat;
+                    let dlon = lon_p - lon;
+                    dlat * dlat + dlon * dlon
+                };
+                dist(*lat_a, *lon_a)
+                    .partial_cmp(&dist(*lat_b, *lon_b))
+                    .unwrap()
+            })
+            .map(|(_, _, _, location)| *location)
+            .ok_or_else(|| Error::NotFound {
+                msg: format!("no known tenki.jp point near ({}, {})", lat, lon),
+            })
+    }
+
+    /// 接続元のグローバルIPから位置を推定し、最寄りの地点コードを返す。
+    /// 取得に失敗した場合は`default`にフォールバックする。
+    pub fn autolocate(default: Location) -> Self {
+        match Self::autolocate_inner() {
+            Ok(location) => location,
+            Err(_) => default,
+        }
+    }
+
+    fn autolocate_inner() -> Result<Self> {
+        let response: serde_json::Value = reqwest::blocking::get("https://ipapi.co/json/")
+            .map_err(|e| Error::NetworkError {
+                msg: format!("{}", e),
+            })?
+            .json()
+            .map_err(|e| Error::NetworkError {
+                msg: format!("{}", e),
+            })?;
+
+        let lat = response["latitude"]
+            .as_f64()
+            .ok_or_else(|| Error::NotFound {
+                msg: "ipapi.co response missing latitude".to_owned(),
+            })?;
+        let lon = response["longitude"]
+            .as_f64()
+            .ok_or_else(|| Error::NotFound {
+                msg: "ipapi.co response missing longitude".to_owned(),
+            })?;
+
+        Self::from_lat_lon(lat, lon)
+    }
+}
+
+impl Default for Location {
+    /// 明示的な地点指定がない場合のデフォルトはこれまで通り東京。
+    fn default() -> Self {
+        Location::TOKYO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_city_name() {
+        assert_eq!(Location::from_city_name("東京").unwrap(), Location::TOKYO);
+        assert!(Location::from_city_name("存在しない街").is_err());
+    }
+
+    #[test]
+    fn test_from_lat_lon_picks_nearest() {
+        // 新宿区役所付近の座標 -> 東京の地点コードが選ばれるはず
+        let location = Location::from_lat_lon(35.6938, 139.7036).unwrap();
+        assert_eq!(location, Location::TOKYO);
+    }
+
+    #[test]
+    fn test_path_format() {
+        assert_eq!(Location::TOKYO.path(), "3/11/4020/8220");
+    }
+}
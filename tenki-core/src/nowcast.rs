@@ -0,0 +1,124 @@
+//! 時間別予報から降水量だけを取り出した時系列（ナウキャスト用）。
+//!
+//! ステータスバーの簡易グラフなどは表形式の`DailyForecast`をそのまま舐めるより、
+//! 時刻と降水量の組だけが欲しいことが多い。このモジュールはそれを
+//! `fetch_each_3hours_forecast`/`fetch_each_1hour_forecast`が返す3日分の配列から
+//! 1本の時系列にまとめる。
+
+use crate::weather::{Announce, DailyForecast};
+use chrono::{DateTime, Local, TimeZone};
+
+/// `[DailyForecast]`から降水量の時系列を取り出すための拡張トレイト。
+pub trait PrecipitationTimeline {
+    /// 3日分の`weathers`を時系列順に1本へ平坦化する。
+    ///
+    /// `Announce::NotYet`（未発表）は常に除外する。`include_past`が`false`なら
+    /// `Announce::Past`（既に過ぎた時間帯）も除外し、これからの時間帯だけを返す。
+    fn precipitation_timeline(&self, include_past: bool) -> Vec<(DateTime<Local>, f32)>;
+}
+
+impl PrecipitationTimeline for [DailyForecast] {
+    fn precipitation_timeline(&self, include_past: bool) -> Vec<(DateTime<Local>, f32)> {
+        let mut points: Vec<_> = self
+            .iter()
+            .flat_map(|day| {
+                day.weathers.iter().filter_map(move |(time, announce)| {
+                    let weather = match announce {
+                        Announce::NotYet => None,
+                        Announce::Past(weather) => include_past.then_some(weather),
+                        Announce::Regular(weather) => Some(weather),
+                    }?;
+
+                    let datetime = Local
+                        .from_local_datetime(&day.date.and_time(*time))
+                        .single()?;
+                    Some((datetime, weather.precipitation))
+                })
+            })
+            .collect();
+
+        points.sort_by_key(|(datetime, _)| *datetime);
+        points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::{Weather, WeatherKind, WindDirection};
+
+    fn weather(precipitation: f32) -> Weather {
+        Weather {
+            kind: WeatherKind::Rain,
+            temperature: 20.0,
+            temperature_low: None,
+            prob_precip: Some(50),
+            precipitation,
+            humidity: 80,
+            wind_direction: WindDirection::South,
+            wind_speed: 3.0,
+        }
+    }
+
+    fn day(date: chrono::NaiveDate, weathers: Vec<(chrono::NaiveTime, Announce)>) -> DailyForecast {
+        DailyForecast {
+            location: "東京".to_owned(),
+            date,
+            weathers,
+        }
+    }
+
+    #[test]
+    fn test_skips_not_yet_and_sorts_chronologically() {
+        let day1 = day(
+            chrono::NaiveDate::from_ymd(2026, 7, 26),
+            vec![
+                (
+                    chrono::NaiveTime::from_hms(12, 0, 0),
+                    Announce::Regular(weather(2.0)),
+                ),
+                (chrono::NaiveTime::from_hms(15, 0, 0), Announce::NotYet),
+                (
+                    chrono::NaiveTime::from_hms(9, 0, 0),
+                    Announce::Past(weather(0.5)),
+                ),
+            ],
+        );
+        let day2 = day(
+            chrono::NaiveDate::from_ymd(2026, 7, 27),
+            vec![(
+                chrono::NaiveTime::from_hms(0, 0, 0),
+                Announce::Regular(weather(1.0)),
+            )],
+        );
+
+        let days = [day1, day2];
+        let timeline = days.precipitation_timeline(false);
+
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[0].1, 2.0);
+        assert_eq!(timeline[1].1, 1.0);
+        assert!(timeline[0].0 < timeline[1].0);
+    }
+
+    #[test]
+    fn test_include_past() {
+        let day1 = day(
+            chrono::NaiveDate::from_ymd(2026, 7, 26),
+            vec![
+                (
+                    chrono::NaiveTime::from_hms(9, 0, 0),
+                    Announce::Past(weather(0.5)),
+                ),
+                (
+                    chrono::NaiveTime::from_hms(12, 0, 0),
+                    Announce::Regular(weather(2.0)),
+                ),
+            ],
+        );
+
+        let days = [day1];
+        assert_eq!(days.precipitation_timeline(true).len(), 2);
+        assert_eq!(days.precipitation_timeline(false).len(), 1);
+    }
+}
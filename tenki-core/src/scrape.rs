@@ -1,7 +1,10 @@
+use crate::cache::{Cache, DEFAULT_TTL};
+use crate::location::Location;
 use crate::weather::{Announce, DailyForecast, Weather, WeatherKind, WindDirection};
 use chrono::prelude::*;
 use itertools::izip;
 use scraper::{Html, Selector};
+use std::sync::OnceLock;
 
 #[derive(Debug)]
 pub enum Error {
@@ -19,20 +22,17 @@ impl std::fmt::Display for Error {
     }
 }
 
-fn fetch_3days_forecast(h: u8) -> Result<Box<[DailyForecast; 3]>> {
+fn url_3days(h: u8, location: &Location) -> String {
     assert!(h == 1 || h == 3);
-
-    let url = format!(
-        "https://tenki.jp/forecast/3/11/4020/8220/{}.html",
+    format!(
+        "https://tenki.jp/forecast/{}/{}.html",
+        location.path(),
         if h == 3 { "3hours" } else { "1hour" }
-    );
-    let html = reqwest::blocking::get(url.as_str())
-        .map_err(|e| Error::NetworkError {
-            msg: format!("{}", e),
-        })?
-        .text_with_charset("utf-8")
-        .unwrap();
-    let document = Html::parse_document(&html);
+    )
+}
+
+fn parse_3days_html(html: &str, h: u8) -> Result<Box<[DailyForecast; 3]>> {
+    let document = Html::parse_document(html);
 
     let selector_location_announced_time = Selector::parse("h2").unwrap();
     let selector_tables = Selector::parse(
@@ -129,6 +129,7 @@ fn fetch_3days_forecast(h: u8) -> Result<Box<[DailyForecast; 3]>> {
                             let weather = Weather {
                                 kind: parse(&collect_text(kind), "kind")?,
                                 temperature: parse(&collect_text(temp), "temp")?,
+                                temperature_low: None,
                                 prob_precip: collect_text(prob_precip).parse().ok(),
                                 precipitation: parse(&collect_text(precip), "precipitation")?,
                                 humidity: parse(&collect_text(humid), "humidity")?,
@@ -154,22 +155,290 @@ fn fetch_3days_forecast(h: u8) -> Result<Box<[DailyForecast; 3]>> {
     .map_err(|e| Error::InvalidHtml { msg: e })
 }
 
-/// 3時間天気
+fn cache_3hours() -> &'static Cache<Box<[DailyForecast; 3]>> {
+    static CACHE: OnceLock<Cache<Box<[DailyForecast; 3]>>> = OnceLock::new();
+    CACHE.get_or_init(|| Cache::new(DEFAULT_TTL))
+}
+
+fn cache_1hour() -> &'static Cache<Box<[DailyForecast; 3]>> {
+    static CACHE: OnceLock<Cache<Box<[DailyForecast; 3]>>> = OnceLock::new();
+    CACHE.get_or_init(|| Cache::new(DEFAULT_TTL))
+}
+
+fn fetch_each_3hours_forecast_uncached(location: &Location) -> Result<Box<[DailyForecast; 3]>> {
+    let html = reqwest::blocking::get(url_3days(3, location).as_str())
+        .map_err(|e| Error::NetworkError {
+            msg: format!("{}", e),
+        })?
+        .text_with_charset("utf-8")
+        .unwrap();
+    parse_3days_html(&html, 3)
+}
+
+fn fetch_each_1hour_forecast_uncached(location: &Location) -> Result<Box<[DailyForecast; 3]>> {
+    let html = reqwest::blocking::get(url_3days(1, location).as_str())
+        .map_err(|e| Error::NetworkError {
+            msg: format!("{}", e),
+        })?
+        .text_with_charset("utf-8")
+        .unwrap();
+    parse_3days_html(&html, 1)
+}
+
+/// 3時間天気。直近の結果が[`DEFAULT_TTL`]以内ならキャッシュを返す。
 #[allow(dead_code)]
-pub fn fetch_each_3hours_forecast() -> Result<Box<[DailyForecast; 3]>> {
-    fetch_3days_forecast(3)
+pub fn fetch_each_3hours_forecast(location: &Location) -> Result<Box<[DailyForecast; 3]>> {
+    cache_3hours().get_or_fetch(location, || fetch_each_3hours_forecast_uncached(location))
 }
 
-/// 1時間天気
+/// 1時間天気。直近の結果が[`DEFAULT_TTL`]以内ならキャッシュを返す。
 #[allow(dead_code)]
-pub fn fetch_each_1hour_forecast() -> Result<Box<[DailyForecast; 3]>> {
-    fetch_3days_forecast(1)
+pub fn fetch_each_1hour_forecast(location: &Location) -> Result<Box<[DailyForecast; 3]>> {
+    cache_1hour().get_or_fetch(location, || fetch_each_1hour_forecast_uncached(location))
 }
 
-/// 10日間天気
+/// 3時間天気（非同期版）。キャッシュの読み書き自体は同期だが、
+/// ミスした場合のフェッチは非同期で行われる。
 #[allow(dead_code)]
-pub fn fetch_10days() -> Result<Box<[DailyForecast; 10]>> {
-    todo!()
+pub async fn fetch_each_3hours_forecast_async(
+    location: &Location,
+) -> Result<Box<[DailyForecast; 3]>> {
+    if let Some(cached) = cache_3hours().peek(location) {
+        return Ok(cached);
+    }
+
+    let html = reqwest::get(url_3days(3, location).as_str())
+        .await
+        .map_err(|e| Error::NetworkError {
+            msg: format!("{}", e),
+        })?
+        .text_with_charset("utf-8")
+        .await
+        .unwrap();
+    let forecast = parse_3days_html(&html, 3)?;
+    cache_3hours().store(location, forecast.clone());
+    Ok(forecast)
+}
+
+/// 1時間天気（非同期版）。キャッシュの読み書き自体は同期だが、
+/// ミスした場合のフェッチは非同期で行われる。
+#[allow(dead_code)]
+pub async fn fetch_each_1hour_forecast_async(
+    location: &Location,
+) -> Result<Box<[DailyForecast; 3]>> {
+    if let Some(cached) = cache_1hour().peek(location) {
+        return Ok(cached);
+    }
+
+    let html = reqwest::get(url_3days(1, location).as_str())
+        .await
+        .map_err(|e| Error::NetworkError {
+            msg: format!("{}", e),
+        })?
+        .text_with_charset("utf-8")
+        .await
+        .unwrap();
+    let forecast = parse_3days_html(&html, 1)?;
+    cache_1hour().store(location, forecast.clone());
+    Ok(forecast)
+}
+
+fn url_10days(location: &Location) -> String {
+    format!("https://tenki.jp/forecast/{}/10days.html", location.path())
+}
+
+fn parse_10days_html(html: &str) -> Result<Box<[DailyForecast; 10]>> {
+    let document = Html::parse_document(html);
+
+    let selector_location_announced_time = Selector::parse("h2").unwrap();
+    let selector_table = Selector::parse("#forecast-point-10days-week").unwrap();
+    let selector_date = Selector::parse("tr.date > td").unwrap();
+    let selector_kind = Selector::parse("tr.weather > td").unwrap();
+    let selector_temp_high = Selector::parse("tr.temp-high > td").unwrap();
+    let selector_temp_low = Selector::parse("tr.temp-low > td").unwrap();
+    let selector_prob_precip = Selector::parse("tr.prob-precip > td").unwrap();
+
+    || -> std::result::Result<_, String> {
+        let (location_name, announced_time) = {
+            let mut text = document
+                .select(&selector_location_announced_time)
+                .next()
+                .ok_or_else(|| "location, announced_time not found")?
+                .text();
+            let location_name = text.next().ok_or_else(|| "location not found")?;
+            let announced_time = text.next().ok_or_else(|| "announced_time not found")?;
+            (location_name, announced_time)
+        };
+
+        let table = document
+            .select(&selector_table)
+            .next()
+            .ok_or_else(|| "10days table not found")?;
+        let table = Html::parse_fragment(&table.html());
+
+        let local_today = chrono::Local::today();
+        let date_regex = regex::Regex::new(r#"(\d+)/(\d+)"#).unwrap();
+        let parse_date = |input: &str| -> Option<chrono::NaiveDate> {
+            let grp = date_regex.captures(input)?;
+            let m: u32 = grp.get(1)?.as_str().parse().unwrap();
+            let d: u32 = grp.get(2)?.as_str().parse().unwrap();
+            // check year wrapping
+            // NOTE: is this always correct?
+            let y: i32 = if m == 1 && local_today.month() == 12 {
+                local_today.year() + 1
+            } else {
+                local_today.year()
+            };
+            Some(chrono::NaiveDate::from_ymd(y, m, d))
+        };
+
+        fn collect_text(elem: scraper::ElementRef) -> String {
+            elem.text().collect::<String>().trim().to_owned()
+        }
+        fn parse<T>(s: &str, name: &str) -> std::result::Result<T, String>
+        where
+            T: std::str::FromStr,
+        {
+            s.parse()
+                .map_err(|_| format!("Failed to parse {:?} as {}", s, name))
+        }
+
+        // 10日間予報の天気欄は`tr.weather > td`がテキストではなく<img alt="...">の
+        // アイコンで表されるので、まずimgのalt/titleを見る。どちらも無ければ
+        // （本来は起こらないはずだが）テキストにフォールバックする。
+        fn kind_label(elem: scraper::ElementRef) -> String {
+            let selector_img = Selector::parse("img").unwrap();
+            if let Some(img) = elem.select(&selector_img).next() {
+                if let Some(alt) = img.value().attr("alt").filter(|s| !s.is_empty()) {
+                    return alt.trim().to_owned();
+                }
+                if let Some(title) = img.value().attr("title").filter(|s| !s.is_empty()) {
+                    return title.trim().to_owned();
+                }
+            }
+            collect_text(elem)
+        }
+
+        // 気温セルは"28℃"のように単位付きで入っているので、数値の後ろに
+        // くっついた単位・全角文字を取り除いてからパースする。
+        fn parse_temperature(s: &str, name: &str) -> std::result::Result<f32, String> {
+            let trimmed = s.trim().trim_end_matches(|c: char| !c.is_ascii_digit());
+            trimmed
+                .parse()
+                .map_err(|_| format!("Failed to parse {:?} as {}", s, name))
+        }
+
+        // izip!は最も短い列に合わせて黙って切り詰めてしまうので、そうなる前に
+        // 各行セレクタの件数を突き合わせ、食い違っていればどの行が何件だったかを
+        // エラーに残す。tenki.jpがレイアウトを変えてtd数が変わった場合の手がかりになる。
+        let row_counts = [
+            ("tr.date", table.select(&selector_date).count()),
+            ("tr.weather", table.select(&selector_kind).count()),
+            ("tr.temp-high", table.select(&selector_temp_high).count()),
+            ("tr.temp-low", table.select(&selector_temp_low).count()),
+            ("tr.prob-precip", table.select(&selector_prob_precip).count()),
+        ];
+        let expected = row_counts[0].1;
+        if let Some((name, count)) = row_counts.iter().find(|(_, count)| *count != expected) {
+            return Err(format!(
+                "10days table row count mismatch: {} has {} cells but {} has {} (page layout changed?)",
+                name, count, row_counts[0].0, expected
+            ));
+        }
+
+        izip!(
+            table.select(&selector_date),
+            table.select(&selector_kind),
+            table.select(&selector_temp_high),
+            table.select(&selector_temp_low),
+            table.select(&selector_prob_precip),
+        )
+        .map(|(date, kind, temp_high, temp_low, prob_precip)| {
+            let date = parse_date(&collect_text(date)).ok_or("invalid date")?;
+            let kind_label = kind_label(kind);
+            // 未発表の日はアイコンが出ず欄が空になる（従来の"---"表記の日があれば
+            // それも後方互換として拾う）。
+            let not_yet = kind_label.is_empty() || kind_label == "---";
+
+            let announce = if not_yet {
+                Announce::NotYet
+            } else {
+                Announce::Regular(Weather {
+                    kind: parse(&kind_label, "kind")?,
+                    temperature: parse_temperature(&collect_text(temp_high), "temp_high")?,
+                    temperature_low: Some(parse_temperature(
+                        &collect_text(temp_low),
+                        "temp_low",
+                    )?),
+                    prob_precip: collect_text(prob_precip).parse().ok(),
+                    // 10日間予報のページには降水量・湿度・風向風速は載っていない
+                    precipitation: 0.0,
+                    humidity: 0,
+                    wind_direction: WindDirection::Calm,
+                    wind_speed: 0.0,
+                })
+            };
+
+            Ok(DailyForecast {
+                location: format!("{} ({})", location_name, announced_time),
+                date,
+                weathers: vec![(chrono::NaiveTime::from_hms(0, 0, 0), announce)],
+            })
+        })
+        .collect::<std::result::Result<Vec<_>, String>>()
+    }()
+    .map_err(|e| Error::InvalidHtml { msg: e })
+    .and_then(|forecasts| {
+        use std::convert::TryInto;
+        let actual = forecasts.len();
+        forecasts
+            .into_boxed_slice()
+            .try_into()
+            .map_err(|_| Error::InvalidHtml {
+                msg: format!("expected exactly 10 days in the 10days table, got {}", actual),
+            })
+    })
+}
+
+fn cache_10days() -> &'static Cache<Box<[DailyForecast; 10]>> {
+    static CACHE: OnceLock<Cache<Box<[DailyForecast; 10]>>> = OnceLock::new();
+    CACHE.get_or_init(|| Cache::new(DEFAULT_TTL))
+}
+
+fn fetch_10days_uncached(location: &Location) -> Result<Box<[DailyForecast; 10]>> {
+    let html = reqwest::blocking::get(url_10days(location).as_str())
+        .map_err(|e| Error::NetworkError {
+            msg: format!("{}", e),
+        })?
+        .text_with_charset("utf-8")
+        .unwrap();
+    parse_10days_html(&html)
+}
+
+/// 10日間天気。直近の結果が[`DEFAULT_TTL`]以内ならキャッシュを返す。
+#[allow(dead_code)]
+pub fn fetch_10days(location: &Location) -> Result<Box<[DailyForecast; 10]>> {
+    cache_10days().get_or_fetch(location, || fetch_10days_uncached(location))
+}
+
+/// 10日間天気（非同期版）
+#[allow(dead_code)]
+pub async fn fetch_10days_async(location: &Location) -> Result<Box<[DailyForecast; 10]>> {
+    if let Some(cached) = cache_10days().peek(location) {
+        return Ok(cached);
+    }
+
+    let html = reqwest::get(url_10days(location).as_str())
+        .await
+        .map_err(|e| Error::NetworkError {
+            msg: format!("{}", e),
+        })?
+        .text_with_charset("utf-8")
+        .await
+        .unwrap();
+    let forecast = parse_10days_html(&html)?;
+    cache_10days().store(location, forecast.clone());
+    Ok(forecast)
 }
 
 #[cfg(test)]
@@ -178,17 +447,55 @@ mod tests {
 
     #[test]
     fn test_fetch_3days() {
-        match fetch_each_3hours_forecast() {
+        let location = Location::TOKYO;
+        match fetch_each_3hours_forecast(&location) {
+            Err(Error::InvalidHtml { msg }) => {
+                panic!("page layout updated? msg = {}", msg);
+            }
+            _ => {}
+        }
+        match fetch_each_1hour_forecast(&location) {
+            Err(Error::InvalidHtml { msg }) => {
+                panic!("page layout updated? msg = {}", msg);
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_fetch_10days() {
+        match fetch_10days(&Location::TOKYO) {
+            Err(Error::InvalidHtml { msg }) => {
+                panic!("page layout updated? msg = {}", msg);
+            }
+            _ => {}
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_3days_async() {
+        let location = Location::TOKYO;
+        match fetch_each_3hours_forecast_async(&location).await {
             Err(Error::InvalidHtml { msg }) => {
                 panic!("page layout updated? msg = {}", msg);
             }
             _ => {}
         }
-        match fetch_each_1hour_forecast() {
+        match fetch_each_1hour_forecast_async(&location).await {
             Err(Error::InvalidHtml { msg }) => {
                 panic!("page layout updated? msg = {}", msg);
             }
             _ => {}
         }
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_fetch_10days_async() {
+        match fetch_10days_async(&Location::TOKYO).await {
+            Err(Error::InvalidHtml { msg }) => {
+                panic!("page layout updated? msg = {}", msg);
+            }
+            _ => {}
+        }
+    }
+}
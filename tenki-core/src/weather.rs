@@ -0,0 +1,202 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 1地点・1日分の天気予報
+///
+/// フィールド名はそのままJSONのキー名になる（snake_case）。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyForecast {
+    pub location: String,
+    pub date: NaiveDate,
+    pub weathers: Vec<(NaiveTime, Announce)>,
+}
+
+/// ある時刻の天気
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Weather {
+    pub kind: WeatherKind,
+    pub temperature: f32,
+    /// 日別予報（`fetch_10days`）でのみ使われる最低気温。
+    /// 時間別予報では`temperature`1本しか得られないため`None`になる。
+    pub temperature_low: Option<f32>,
+    pub prob_precip: Option<u8>,
+    pub precipitation: f32,
+    pub humidity: u8,
+    pub wind_direction: WindDirection,
+    pub wind_speed: f32,
+}
+
+/// tenki.jpはまだ発表されていない時間帯を"---"で埋めたり、
+/// 過去の時間帯の時刻欄に`past`クラスを付けて表現したりする。
+/// それをそのまま区別して持っておく。
+///
+/// JSON上では`{"status": "regular", "weather": {...}}`のように
+/// `status`/`weather`のアジャセントタグ付けで表現される。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", content = "weather", rename_all = "snake_case")]
+pub enum Announce {
+    /// まだ発表されていない
+    NotYet,
+    /// 発表済みで、既に過ぎた時間帯
+    Past(Weather),
+    /// 発表済みで、これからの時間帯
+    Regular(Weather),
+}
+
+/// 天気種別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherKind {
+    Sunny,
+    SunnyOccasionallyCloudy,
+    SunnyTemporarilyCloudy,
+    SunnyOccasionallyRain,
+    SunnyTemporarilyRain,
+    Cloudy,
+    CloudyOccasionallySunny,
+    CloudyTemporarilySunny,
+    CloudyOccasionallyRain,
+    CloudyTemporarilyRain,
+    Rain,
+    RainOccasionallySunny,
+    RainOccasionallyCloudy,
+    Snow,
+    SnowOccasionallySunny,
+    SnowOccasionallyCloudy,
+    Thunderstorm,
+    Fog,
+}
+
+impl std::str::FromStr for WeatherKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        use WeatherKind::*;
+        Ok(match s {
+            "晴" => Sunny,
+            "晴時々曇" => SunnyOccasionallyCloudy,
+            "晴一時曇" => SunnyTemporarilyCloudy,
+            "晴時々雨" => SunnyOccasionallyRain,
+            "晴一時雨" => SunnyTemporarilyRain,
+            "曇" => Cloudy,
+            "曇時々晴" => CloudyOccasionallySunny,
+            "曇一時晴" => CloudyTemporarilySunny,
+            "曇時々雨" => CloudyOccasionallyRain,
+            "曇一時雨" => CloudyTemporarilyRain,
+            "雨" => Rain,
+            "雨時々晴" => RainOccasionallySunny,
+            "雨時々曇" => RainOccasionallyCloudy,
+            "雪" => Snow,
+            "雪時々晴" => SnowOccasionallySunny,
+            "雪時々曇" => SnowOccasionallyCloudy,
+            "雷" => Thunderstorm,
+            "霧" => Fog,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl std::fmt::Display for WeatherKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use WeatherKind::*;
+        let label = match self {
+            Sunny => "晴れ",
+            SunnyOccasionallyCloudy => "晴れ時々曇り",
+            SunnyTemporarilyCloudy => "晴れ一時曇り",
+            SunnyOccasionallyRain => "晴れ時々雨",
+            SunnyTemporarilyRain => "晴れ一時雨",
+            Cloudy => "曇り",
+            CloudyOccasionallySunny => "曇り時々晴れ",
+            CloudyTemporarilySunny => "曇り一時晴れ",
+            CloudyOccasionallyRain => "曇り時々雨",
+            CloudyTemporarilyRain => "曇り一時雨",
+            Rain => "雨",
+            RainOccasionallySunny => "雨時々晴れ",
+            RainOccasionallyCloudy => "雨時々曇り",
+            Snow => "雪",
+            SnowOccasionallySunny => "雪時々晴れ",
+            SnowOccasionallyCloudy => "雪時々曇り",
+            Thunderstorm => "雷",
+            Fog => "霧",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// 16方位の風向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindDirection {
+    North,
+    NorthNortheast,
+    Northeast,
+    EastNortheast,
+    East,
+    EastSoutheast,
+    Southeast,
+    SouthSoutheast,
+    South,
+    SouthSouthwest,
+    Southwest,
+    WestSouthwest,
+    West,
+    WestNorthwest,
+    Northwest,
+    NorthNorthwest,
+    /// 静穏（ほぼ無風）
+    Calm,
+}
+
+impl std::str::FromStr for WindDirection {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        use WindDirection::*;
+        Ok(match s {
+            "北" => North,
+            "北北東" => NorthNortheast,
+            "北東" => Northeast,
+            "東北東" => EastNortheast,
+            "東" => East,
+            "東南東" => EastSoutheast,
+            "南東" => Southeast,
+            "南南東" => SouthSoutheast,
+            "南" => South,
+            "南南西" => SouthSouthwest,
+            "南西" => Southwest,
+            "西南西" => WestSouthwest,
+            "西" => West,
+            "西北西" => WestNorthwest,
+            "北西" => Northwest,
+            "北北西" => NorthNorthwest,
+            "静穏" | "－" | "-" => Calm,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl std::fmt::Display for WindDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use WindDirection::*;
+        let label = match self {
+            North => "北",
+            NorthNortheast => "北北東",
+            Northeast => "北東",
+            EastNortheast => "東北東",
+            East => "東",
+            EastSoutheast => "東南東",
+            Southeast => "南東",
+            SouthSoutheast => "南南東",
+            South => "南",
+            SouthSouthwest => "南南西",
+            Southwest => "南西",
+            WestSouthwest => "西南西",
+            West => "西",
+            WestNorthwest => "西北西",
+            Northwest => "北西",
+            NorthNorthwest => "北北西",
+            Calm => "静穏",
+        };
+        write!(f, "{}", label)
+    }
+}